@@ -1,73 +1,443 @@
 // Prevents additional console window on Windows in release, DO NOT REMOVE!!
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-use std::process::{Command, Stdio};
+use std::env;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpStream;
+use std::path::PathBuf;
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender};
+use std::sync::Mutex;
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
-fn start_python_agent() -> Result<(), Box<dyn std::error::Error>> {
+use tauri::{AppHandle, Emitter, Manager, RunEvent, State};
+
+/// A single line of agent output, forwarded to the webview as an `agent-log`
+/// event so the UI can render and filter a live log panel.
+#[derive(Clone, serde::Serialize)]
+struct AgentLog {
+    /// Milliseconds since the Unix epoch when the line was read.
+    timestamp: u64,
+    /// Which stream the line came from: `"stdout"` or `"stderr"`.
+    stream: &'static str,
+    /// The log line, with its trailing newline stripped.
+    line: String,
+}
+
+/// Address the Python agent's HTTP server listens on.
+const AGENT_ADDR: &str = "127.0.0.1:8000";
+
+/// How long to wait for the agent to answer its health endpoint before giving up.
+const AGENT_READY_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Upper bound on in-flight control messages; a stuck agent applies
+/// backpressure to the frontend rather than letting the queue grow unbounded.
+const CONTROL_QUEUE_CAPACITY: usize = 64;
+
+/// The Python agent child process, kept in Tauri's managed state so it can be
+/// shut down cleanly when the desktop shell exits.
+struct AgentProcess(Mutex<Option<Child>>);
+
+/// Lifecycle commands the desktop shell can send to the running agent.
+enum AgentCommand {
+    /// Ask the agent to re-read its configuration.
+    ReloadConfig,
+    /// Kill the current agent and spawn a fresh one atomically.
+    Restart,
+    /// Ask the agent to stop gracefully, then tear the child down.
+    Stop,
+}
+
+/// Sending half of the bounded control channel, kept in managed state alongside
+/// [`AgentProcess`] so restart can kill-and-respawn the tracked child.
+struct AgentControl(SyncSender<AgentCommand>);
+
+/// Locate a Python interpreter on `PATH`, modelled on rustc's `x` launcher.
+///
+/// Each candidate name is searched for across every `PATH` directory (with the
+/// platform executable extension appended). A bare `python` wins as soon as it
+/// is found; otherwise a discovered `python3` is preferred over `python2`.
+fn find_python() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let path = env::var_os("PATH").ok_or("PATH is not set")?;
+    let dirs: Vec<PathBuf> = env::split_paths(&path).collect();
+
+    let find = |name: &str| -> Option<PathBuf> {
+        for dir in &dirs {
+            let mut candidate = dir.join(name);
+            candidate.set_extension(env::consts::EXE_EXTENSION);
+            if candidate.is_file() {
+                return Some(candidate);
+            }
+        }
+        None
+    };
+
+    find("python")
+        .or_else(|| find("python3"))
+        .or_else(|| find("python2"))
+        .ok_or_else(|| "no Python interpreter (python/python3/python2) found on PATH".into())
+}
+
+/// Start the Python agent and hand it to [`AgentProcess`] managed state as
+/// soon as it's spawned — before the readiness wait below, not after — so a
+/// concurrent shutdown can always observe and tear down whatever is actually
+/// running instead of finding `None` for the up-to-`AGENT_READY_TIMEOUT`
+/// duration of that wait.
+fn start_python_agent(app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
     println!("🚀 Starting Python agent...");
-    
-    // Get the current executable directory
-    let mut agent_path = std::env::current_exe()?;
-    agent_path.pop(); // Remove the executable name
-    
-    // In development mode, the agent is in the agent/dist directory relative to project root
-    // In production, it should be bundled with the app
-    if cfg!(debug_assertions) {
-        // Development mode: go up to project root, then to agent/dist
-        agent_path.pop(); // Remove target
-        agent_path.pop(); // Remove debug
-        agent_path.pop(); // Remove target
-        agent_path.push("agent");
-        agent_path.push("dist");
-        agent_path.push("lise-agent.exe");
-    } else {
-        // Production mode: agent should be in the same directory or resources
-        agent_path.push("lise-agent.exe");
-    }
-    
+
+    let agent_path = resolve_agent_binary(app)?;
     println!("Looking for agent at: {:?}", agent_path);
-    
-    if !agent_path.exists() {
-        return Err(format!("Python agent not found at: {:?}", agent_path).into());
-    }
-    
+
+    let mut command = if agent_path.exists() {
+        Command::new(&agent_path)
+    } else {
+        // No PyInstaller build present (e.g. a fresh dev checkout): fall back to
+        // running the agent's entry script with a discovered interpreter so the
+        // desktop shell still boots.
+        let python = find_python()?;
+        let script = agent_entry_script();
+        if !script.exists() {
+            return Err(format!(
+                "neither the bundled agent ({:?}) nor its entry script ({:?}) exists",
+                agent_path, script
+            )
+            .into());
+        }
+        println!("Bundled agent missing, falling back to {:?} {:?}", python, script);
+        let mut command = Command::new(python);
+        command.arg(script);
+        command
+    };
+
     // Start the Python agent as a background process
-    let mut child = Command::new(&agent_path)
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
-        .spawn()?;
-    
+    let mut child = spawn_agent(&mut command)?;
+
     println!("✓ Python agent started with PID: {}", child.id());
-    
-    // Wait a moment for the agent to start
-    thread::sleep(Duration::from_secs(2));
-    
-    // Check if the process is still running
-    match child.try_wait() {
-        Ok(Some(status)) => {
-            return Err(format!("Python agent exited early with status: {}", status).into());
+
+    // Drain the child's pipes on dedicated threads, forwarding each line to the
+    // webview. This must happen before the readiness wait so the agent can't
+    // deadlock by filling an unread pipe buffer during startup.
+    if let Some(stdout) = child.stdout.take() {
+        forward_stream(app.clone(), stdout, "stdout");
+    }
+    if let Some(stderr) = child.stderr.take() {
+        forward_stream(app.clone(), stderr, "stderr");
+    }
+
+    *app.state::<AgentProcess>().0.lock().unwrap() = Some(child);
+
+    // Poll the agent's health endpoint until it answers, bailing out early if
+    // the process dies during startup or is taken over by a concurrent
+    // shutdown.
+    wait_for_agent_ready(app, AGENT_READY_TIMEOUT)?;
+    println!("✓ Python agent is ready");
+
+    Ok(())
+}
+
+/// Poll the agent's HTTP health endpoint every ~100ms until it responds or
+/// `timeout` elapses. The poll is interleaved with `try_wait` (taken on the
+/// managed [`AgentProcess`], which `start_python_agent` populates before
+/// calling this) so an agent that dies — or is claimed by a concurrent
+/// shutdown — during startup is reported immediately instead of after the
+/// full wait.
+fn wait_for_agent_ready(
+    app: &AppHandle,
+    timeout: Duration,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        {
+            let mut guard = app.state::<AgentProcess>().0.lock().unwrap();
+            match guard.as_mut() {
+                Some(child) => {
+                    if let Some(status) = child.try_wait()? {
+                        return Err(
+                            format!("Python agent exited early with status: {}", status).into()
+                        );
+                    }
+                }
+                None => {
+                    return Err("Python agent was taken over by a concurrent shutdown".into());
+                }
+            }
         }
-        Ok(None) => {
-            println!("✓ Python agent is running");
+        if agent_health_ok() {
+            return Ok(());
         }
-        Err(e) => {
-            return Err(format!("Failed to check agent status: {}", e).into());
+        if Instant::now() >= deadline {
+            return Err(format!(
+                "Python agent did not become ready at http://{} within {:?}",
+                AGENT_ADDR, timeout
+            )
+            .into());
         }
+        thread::sleep(Duration::from_millis(100));
     }
-    
-    Ok(())
+}
+
+/// Attempt a single `GET /health` against the agent, returning `true` only if
+/// it answers with a 2xx status line.
+fn agent_health_ok() -> bool {
+    agent_request_ok("GET", "/health", Duration::from_millis(250))
+}
+
+/// Spawn a thread that does line-buffered reads from `reader` and emits each
+/// line to the webview as a tagged `agent-log` event.
+fn forward_stream<R: Read + Send + 'static>(app: AppHandle, reader: R, stream: &'static str) {
+    thread::spawn(move || {
+        for line in BufReader::new(reader).lines() {
+            match line {
+                Ok(line) => {
+                    let payload = AgentLog {
+                        timestamp: now_millis(),
+                        stream,
+                        line,
+                    };
+                    let _ = app.emit("agent-log", payload);
+                }
+                Err(_) => break,
+            }
+        }
+    });
+}
+
+/// Milliseconds since the Unix epoch, or `0` if the clock is before it.
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Shut the agent down, giving it a chance to exit on its own within `timeout`
+/// before force-killing it, and report the exit status. Callers are expected
+/// to have already asked the agent to stop gracefully (e.g. via
+/// `post_agent("/control/stop")`); this only waits for the voluntary exit and
+/// falls back to `kill()`.
+fn shutdown_agent(child: &mut Child, timeout: Duration) -> std::io::Result<ExitStatus> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(status);
+        }
+        if Instant::now() >= deadline {
+            break;
+        }
+        thread::sleep(Duration::from_millis(100));
+    }
+
+    child.kill()?;
+    child.wait()
+}
+
+/// Append the platform executable extension to a bare binary stem, yielding
+/// `lise-agent` on Linux/macOS and `lise-agent.exe` on Windows.
+fn exe_name(stem: &str) -> String {
+    let mut name = PathBuf::from(stem);
+    name.set_extension(env::consts::EXE_EXTENSION);
+    name.to_string_lossy().into_owned()
+}
+
+/// The project root, inferred from the running executable in development
+/// (`<root>/src-tauri/target/<profile>/<exe>`). Verified by checking for the
+/// `agent` directory that should sit right under it, so a future change to
+/// this layout fails loudly instead of silently resolving to the wrong
+/// directory.
+fn dev_project_root() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let mut root = std::env::current_exe()?;
+    root.pop(); // executable name
+    root.pop(); // profile (debug/release)
+    root.pop(); // target
+    root.pop(); // src-tauri
+
+    if !root.join("agent").is_dir() {
+        return Err(format!(
+            "resolved dev project root {:?} has no `agent` directory; build layout may have changed",
+            root
+        )
+        .into());
+    }
+
+    Ok(root)
+}
+
+/// Resolve the bundled agent binary in a platform-aware way.
+///
+/// In development the PyInstaller build lives under `agent/dist` at the project
+/// root. In production the agent ships as a bundle resource; Tauri resolves the
+/// right location for macOS `.app` bundles and Linux AppImages, and we fall
+/// back to sitting next to the executable on Windows.
+fn resolve_agent_binary(app: &AppHandle) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let name = exe_name("lise-agent");
+
+    if cfg!(debug_assertions) {
+        let mut path = dev_project_root()?;
+        path.push("agent");
+        path.push("dist");
+        path.push(&name);
+        return Ok(path);
+    }
+
+    if let Ok(resource_dir) = app.path().resource_dir() {
+        let candidate = resource_dir.join(&name);
+        if candidate.exists() {
+            return Ok(candidate);
+        }
+    }
+
+    let mut path = std::env::current_exe()?;
+    path.pop(); // executable name
+    path.push(&name);
+    Ok(path)
+}
+
+/// Path to the agent's Python entry script (`agent/main.py` at the project
+/// root), used when no compiled agent binary is available.
+fn agent_entry_script() -> PathBuf {
+    match dev_project_root() {
+        Ok(mut root) => {
+            root.push("agent");
+            root.push("main.py");
+            root
+        }
+        Err(_) => PathBuf::from("agent").join("main.py"),
+    }
+}
+
+/// Spawn the agent command with both standard streams piped, keeping process
+/// creation in one cross-platform place. A `LISE_DESKTOP=1` marker lets the
+/// agent detect it is running under the desktop shell.
+fn spawn_agent(command: &mut Command) -> std::io::Result<Child> {
+    command
+        .env("LISE_DESKTOP", "1")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+}
+
+/// Fire a zero-body `POST` at one of the agent's control endpoints, returning
+/// `true` only on a 2xx status line.
+fn post_agent(path: &str) -> bool {
+    agent_request_ok("POST", path, Duration::from_millis(500))
+}
+
+/// Open a short-lived connection to the agent, send a bodyless HTTP/1.1
+/// request line for `method path`, and report whether it answered with a 2xx
+/// status line within `timeout`. Shared by `agent_health_ok` and `post_agent`
+/// so the two control-plane pokes don't hand-roll their own HTTP client.
+fn agent_request_ok(method: &str, path: &str, timeout: Duration) -> bool {
+    let addr = match AGENT_ADDR.parse() {
+        Ok(addr) => addr,
+        Err(_) => return false,
+    };
+    let mut stream = match TcpStream::connect_timeout(&addr, timeout) {
+        Ok(stream) => stream,
+        Err(_) => return false,
+    };
+    let _ = stream.set_read_timeout(Some(timeout));
+
+    let request = format!(
+        "{} {} HTTP/1.1\r\nHost: localhost\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+        method, path
+    );
+    if stream.write_all(request.as_bytes()).is_err() {
+        return false;
+    }
+
+    let mut response = String::new();
+    let _ = stream.read_to_string(&mut response);
+    let status_line = response.lines().next().unwrap_or_default();
+    status_line.starts_with("HTTP/1.1 2") || status_line.starts_with("HTTP/1.0 2")
+}
+
+/// Drain the control channel on a dedicated thread, applying each command to
+/// the agent. Restart takes the old child out of managed state up front, then
+/// relies on `start_python_agent` to store the new one as soon as it's
+/// spawned — the lock is never held across the readiness wait, and the
+/// tracked child is never left `None` for that wait's duration either.
+fn run_control_worker(app: AppHandle, rx: Receiver<AgentCommand>) {
+    thread::spawn(move || {
+        for command in rx {
+            match command {
+                AgentCommand::ReloadConfig => {
+                    if post_agent("/control/reload") {
+                        println!("✓ Sent reload-config to agent");
+                    } else {
+                        eprintln!("⚠️  Failed to reach agent for reload-config");
+                    }
+                }
+                AgentCommand::Stop => {
+                    let _ = post_agent("/control/stop");
+                    let child = app.state::<AgentProcess>().0.lock().unwrap().take();
+                    if let Some(mut child) = child {
+                        if let Err(e) = shutdown_agent(&mut child, Duration::from_secs(5)) {
+                            eprintln!("⚠️  Failed to stop agent: {}", e);
+                        }
+                    }
+                }
+                AgentCommand::Restart => {
+                    let child = app.state::<AgentProcess>().0.lock().unwrap().take();
+                    if let Some(mut child) = child {
+                        let _ = post_agent("/control/stop");
+                        if let Err(e) = shutdown_agent(&mut child, Duration::from_secs(5)) {
+                            eprintln!("⚠️  Failed to stop agent for restart: {}", e);
+                        }
+                    }
+                    match start_python_agent(&app) {
+                        Ok(()) => println!("✓ Python agent restarted"),
+                        Err(e) => eprintln!("❌ Failed to restart Python agent: {}", e),
+                    }
+                }
+            }
+        }
+    });
+}
+
+#[tauri::command]
+fn reload_agent_config(control: State<AgentControl>) -> Result<(), String> {
+    control
+        .0
+        .try_send(AgentCommand::ReloadConfig)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn restart_agent(control: State<AgentControl>) -> Result<(), String> {
+    control
+        .0
+        .try_send(AgentCommand::Restart)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn stop_agent(control: State<AgentControl>) -> Result<(), String> {
+    control
+        .0
+        .try_send(AgentCommand::Stop)
+        .map_err(|e| e.to_string())
 }
 
 fn main() {
-  tauri::Builder::default()
+  let (control_tx, control_rx) = sync_channel::<AgentCommand>(CONTROL_QUEUE_CAPACITY);
+  let mut control_rx = Some(control_rx);
+
+  let app = tauri::Builder::default()
     .plugin(tauri_plugin_shell::init())
-    .setup(|_app| {
+    .manage(AgentProcess(Mutex::new(None)))
+    .manage(AgentControl(control_tx))
+    .invoke_handler(tauri::generate_handler![
+      reload_agent_config,
+      restart_agent,
+      stop_agent
+    ])
+    .setup(move |app| {
       println!("🚀 LISE Agent Desktop starting...");
-      
+
       // Start the Python agent
-      match start_python_agent() {
+      match start_python_agent(app.handle()) {
           Ok(()) => {
               println!("✓ Python agent started successfully");
               println!("🌐 Agent available at http://localhost:8000");
@@ -77,9 +447,42 @@ fn main() {
               eprintln!("Please ensure the Python agent is built and available.");
           }
       }
-      
+
+      // Drain the control channel on a background thread.
+      if let Some(rx) = control_rx.take() {
+          run_control_worker(app.handle().clone(), rx);
+      }
+
       Ok(())
     })
-    .run(tauri::generate_context!())
-    .expect("error while running tauri application");
+    .build(tauri::generate_context!())
+    .expect("error while building tauri application");
+
+  app.run(|app_handle, event| {
+    if let RunEvent::ExitRequested { .. } = event {
+      // Tear the agent down before the shell goes away so we don't leave an
+      // orphaned Python process holding localhost:8000. Ask it to stop
+      // gracefully first, mirroring `AgentCommand::Stop`, before the
+      // wait-then-kill fallback in `shutdown_agent`.
+      let _ = post_agent("/control/stop");
+      let child = app_handle
+        .state::<AgentProcess>()
+        .0
+        .lock()
+        .unwrap()
+        .take();
+      if let Some(mut child) = child {
+        match shutdown_agent(&mut child, Duration::from_secs(5)) {
+          Ok(status) => {
+            println!("✓ Python agent exited with status: {}", status);
+            // Surface a crashing agent as a non-zero desktop exit code.
+            if !status.success() {
+              std::process::exit(status.code().unwrap_or(1));
+            }
+          }
+          Err(e) => eprintln!("⚠️  Failed to shut down Python agent cleanly: {}", e),
+        }
+      }
+    }
+  });
 }